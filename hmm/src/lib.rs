@@ -1,3 +1,8 @@
+/// Tolerance used when checking that a set of probabilities sums to `1.0`.
+/// Float arithmetic rarely lands on the value exactly, so an exact `==`
+/// check rejects otherwise-valid distributions.
+const PROBABILITY_SUM_EPSILON: f64 = 1e-9;
+
 #[derive(Debug)]
 pub struct ProbabilityVector<T, const N: usize> {
     _states: [T; N],
@@ -43,7 +48,7 @@ impl<T, const N: usize> ProbabilityVector<T, N> {
             .copied()
             .sum::<f64>();
 
-        if probability_sum == 1_f64 {
+        if (probability_sum - 1_f64).abs() <= PROBABILITY_SUM_EPSILON {
             // Satisfies callee guarantee that probability sums to 1.
             let pv = unsafe { Self::new_unchecked(src) };
             Some(pv)
@@ -51,6 +56,53 @@ impl<T, const N: usize> ProbabilityVector<T, N> {
             None
         }
     }
+
+    /// Builds a `ProbabilityVector` from non-negative weights by dividing
+    /// each by their total, so any weighted distribution (e.g. counts from
+    /// a trained graph) can be turned into a valid probability vector
+    /// without the caller normalizing by hand. Returns `None` if any weight
+    /// is negative or the weights sum to zero.
+    pub fn try_new_normalized(src: [(T, f64); N]) -> Option<Self> {
+        if src.iter().any(|(_, weight)| *weight < 0.0) {
+            return None;
+        }
+
+        let total: f64 = src.iter().map(|(_, weight)| weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let normalized = src.map(|(state, weight)| (state, weight / total));
+        Self::try_new(normalized)
+    }
+
+    pub fn states(&self) -> &[T; N] {
+        &self._states
+    }
+
+    pub fn probabilities(&self) -> &[f64; N] {
+        &self._probabilities
+    }
+
+    /// Draws a state proportional to its probability, using the same
+    /// cumulative-sum bucket technique as `Graph::sample_next`.
+    pub fn sample<R: rand::Rng>(&self, rng: &mut R) -> &T {
+        let draw: f64 = rng.gen();
+        let mut cumulative = 0.0;
+
+        for (state, probability) in self._states.iter().zip(self._probabilities.iter()) {
+            cumulative += probability;
+            if draw < cumulative {
+                return state;
+            }
+        }
+
+        // Float error can leave `cumulative` fractionally short of `1.0`;
+        // fall back to the last state rather than panicking.
+        self._states
+            .last()
+            .expect("ProbabilityVector is never constructed with N == 0")
+    }
 }
 
 #[cfg(test)]
@@ -68,4 +120,35 @@ mod tests {
 
         assert!(pv.is_some())
     }
+
+    #[test]
+    fn should_normalize_arbitrary_non_negative_weights() {
+        let pv = ProbabilityVector::try_new_normalized([
+            ("north", 1.0),
+            ("south", 4.0),
+            ("east", 3.0),
+            ("west", 2.0),
+        ]);
+
+        assert!(pv.is_some());
+        assert_eq!(
+            [0.1, 0.4, 0.3, 0.2],
+            *pv.unwrap().probabilities()
+        );
+    }
+
+    #[test]
+    fn should_reject_normalizing_all_zero_weights() {
+        let pv = ProbabilityVector::try_new_normalized([("north", 0.0), ("south", 0.0)]);
+
+        assert!(pv.is_none())
+    }
+
+    #[test]
+    fn should_sample_deterministically_when_one_state_holds_all_probability() {
+        let pv = ProbabilityVector::try_new([("north", 1.0), ("south", 0.0)]).unwrap();
+        let mut rng = rand::thread_rng();
+
+        assert_eq!(&"north", pv.sample(&mut rng));
+    }
 }
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::hash::Hash;
 
+use hmm::ProbabilityVector;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PunchKind {
     Jab = 1,
@@ -12,6 +14,15 @@ pub enum PunchKind {
 }
 
 impl PunchKind {
+    pub const ALL: [PunchKind; 6] = [
+        PunchKind::Jab,
+        PunchKind::Cross,
+        PunchKind::LeadHook,
+        PunchKind::RearHook,
+        PunchKind::LeadUppercut,
+        PunchKind::RearUppercut,
+    ];
+
     pub fn as_u8(self) -> u8 {
         u8::from(self)
     }
@@ -110,6 +121,22 @@ impl Graph<(PunchKind, PunchKind), Weight> {
             Some(prev_val)
         }
     }
+
+    /// Increments every consecutive `(previous, next)` edge walked by
+    /// `combo`. A no-op for combos shorter than two punches.
+    pub fn train(&mut self, combo: &Combo) {
+        for window in combo.0.windows(2) {
+            self.insert(&window[0], window[1]);
+        }
+    }
+
+    /// Trains on a batch of recorded combos, e.g. a logged sparring
+    /// session.
+    pub fn train_all<I: IntoIterator<Item = Combo>>(&mut self, combos: I) {
+        for combo in combos {
+            self.train(&combo);
+        }
+    }
 }
 
 impl<K, E: Edge> Graph<K, E>
@@ -123,14 +150,7 @@ where
 
 impl Default for Graph<(PunchKind, PunchKind), Weight> {
     fn default() -> Self {
-        let punches = [
-            PunchKind::Jab,
-            PunchKind::Cross,
-            PunchKind::LeadHook,
-            PunchKind::RearHook,
-            PunchKind::LeadUppercut,
-            PunchKind::RearUppercut,
-        ];
+        let punches = PunchKind::ALL;
 
         let nodes = punches
             .into_iter()
@@ -144,13 +164,288 @@ impl Default for Graph<(PunchKind, PunchKind), Weight> {
     }
 }
 
+impl Graph<(PunchKind, PunchKind), Weight> {
+    /// Draws the next punch following `first`, treating its six outgoing
+    /// edges as a weighted distribution over `Weight`. Returns `None` when
+    /// every outgoing weight is zero, i.e. `first` has never been trained.
+    pub fn sample_next<R: rand::Rng>(&self, first: &PunchKind, rng: &mut R) -> Option<PunchKind> {
+        let weights = PunchKind::ALL.map(|next| (next, self.nodes[&(*first, next)]));
+        let total: usize = weights.iter().map(|(_, weight)| weight.as_usize()).sum();
+
+        if total == 0 {
+            return None;
+        }
+
+        let draw = rng.gen_range(0..total);
+        let mut cumulative = 0;
+        for (next, weight) in weights {
+            cumulative += weight.as_usize();
+            if draw < cumulative {
+                return Some(next);
+            }
+        }
+
+        unreachable!("cumulative weight never reached the drawn value")
+    }
+
+    /// Walks the Markov chain for `len` punches starting from `start`,
+    /// sampling each following punch with [`Self::sample_next`]. Stops early
+    /// if it reaches a punch with no observed outgoing transitions.
+    pub fn generate_combo<R: rand::Rng>(
+        &self,
+        start: PunchKind,
+        len: usize,
+        rng: &mut R,
+    ) -> Combo {
+        let mut punches = Vec::with_capacity(len);
+        if len == 0 {
+            return Combo::new(punches);
+        }
+
+        let mut current = start;
+        punches.push(current);
+
+        while punches.len() < len {
+            match self.sample_next(&current, rng) {
+                Some(next) => {
+                    punches.push(next);
+                    current = next;
+                }
+                None => break,
+            }
+        }
+
+        Combo::new(punches)
+    }
+
+    /// Returns every candidate follow-up punch from `from` in a randomized,
+    /// weighted, non-repeating order (an Efraimidis-style weighted shuffle):
+    /// each remaining candidate's cumulative weight forms a prefix sum, a
+    /// draw in `0..remaining_total` is binary-searched against it to pick
+    /// the next emitted punch, and that candidate is removed before the
+    /// next draw. Once every remaining weight is zero the rest are emitted
+    /// in their current order.
+    pub fn drill_order<R: rand::Rng>(&self, from: &PunchKind, rng: &mut R) -> Vec<PunchKind> {
+        let mut remaining: Vec<(PunchKind, usize)> = PunchKind::ALL
+            .map(|next| (next, self.nodes[&(*from, next)].as_usize()))
+            .to_vec();
+
+        let mut order = Vec::with_capacity(remaining.len());
+
+        while !remaining.is_empty() {
+            let total: usize = remaining.iter().map(|(_, weight)| weight).sum();
+
+            let selected = if total == 0 {
+                0
+            } else {
+                let draw = rng.gen_range(0..total);
+                let prefix: Vec<usize> = remaining
+                    .iter()
+                    .scan(0usize, |cumulative, (_, weight)| {
+                        *cumulative += weight;
+                        Some(*cumulative)
+                    })
+                    .collect();
+
+                prefix.partition_point(|&cumulative| cumulative <= draw)
+            };
+
+            let (punch, _) = remaining.remove(selected);
+            order.push(punch);
+        }
+
+        order
+    }
+
+    /// Decodes the single most likely length-`len` combo starting at
+    /// `start` via Viterbi, converting each outgoing `Weight` into a
+    /// transition cost `-ln((w + 1) / (row_total + 6))` (add-one smoothed
+    /// over the six punch kinds so unseen transitions still have finite
+    /// cost).
+    /// Returns `None` if `start` has no observed outgoing transitions.
+    pub fn most_likely_combo(&self, start: PunchKind, len: usize) -> Option<Combo> {
+        if len == 0 {
+            return Some(Combo::new(Vec::new()));
+        }
+
+        let start_total: usize = PunchKind::ALL
+            .iter()
+            .map(|next| self.nodes[&(start, *next)].as_usize())
+            .sum();
+        if start_total == 0 {
+            return None;
+        }
+
+        let cost = |prev: PunchKind, next: PunchKind| -> f64 {
+            let row_total: usize = PunchKind::ALL
+                .iter()
+                .map(|candidate| self.nodes[&(prev, *candidate)].as_usize())
+                .sum();
+            let weight = self.nodes[&(prev, next)].as_usize();
+
+            -(((weight + 1) as f64) / ((row_total + PunchKind::ALL.len()) as f64)).ln()
+        };
+
+        let index = |punch: PunchKind| -> usize { (punch.as_u8() - 1) as usize };
+
+        let mut dp = vec![[f64::INFINITY; 6]; len];
+        let mut backpointer = vec![[0usize; 6]; len];
+        dp[0][index(start)] = 0.0;
+
+        for t in 1..len {
+            for next in PunchKind::ALL {
+                let mut best = (f64::INFINITY, index(start));
+
+                for prev in PunchKind::ALL {
+                    let candidate_cost = dp[t - 1][index(prev)] + cost(prev, next);
+                    if candidate_cost < best.0 {
+                        best = (candidate_cost, index(prev));
+                    }
+                }
+
+                dp[t][index(next)] = best.0;
+                backpointer[t][index(next)] = best.1;
+            }
+        }
+
+        let last = len - 1;
+        let terminal = PunchKind::ALL
+            .into_iter()
+            .min_by(|a, b| dp[last][index(*a)].total_cmp(&dp[last][index(*b)]))?;
+
+        let mut indices = vec![index(terminal)];
+        for t in (1..len).rev() {
+            indices.push(backpointer[t][*indices.last().unwrap()]);
+        }
+        indices.reverse();
+
+        let punches = indices
+            .into_iter()
+            .map(|i| PunchKind::ALL[i])
+            .collect::<Vec<_>>();
+
+        Some(Combo::new(punches))
+    }
+
+    /// Reads the six outgoing `Weight` counts from `from` and normalizes
+    /// them into a [`ProbabilityVector`], so the counted graph and the
+    /// probability type form one coherent sampling pipeline. Returns `None`
+    /// if `from` has never been trained, i.e. all six weights are zero.
+    pub fn outgoing_distribution(
+        &self,
+        from: &PunchKind,
+    ) -> Option<ProbabilityVector<PunchKind, 6>> {
+        let weights =
+            PunchKind::ALL.map(|next| (next, self.nodes[&(*from, next)].as_usize() as f64));
+
+        ProbabilityVector::try_new_normalized(weights)
+    }
+}
+
+/// A higher-order Markov model keyed on the trailing `ORDER` punches rather
+/// than a single previous punch, so transitions can depend on, say, the
+/// last two strikes instead of just the last one.
+pub type MarkovModel<const ORDER: usize> = Graph<([PunchKind; ORDER], PunchKind), Weight>;
+
+impl<const ORDER: usize> Default for Graph<([PunchKind; ORDER], PunchKind), Weight> {
+    fn default() -> Self {
+        // Eagerly enumerating all `6^ORDER * 6` edges (as the first-order
+        // `Graph` default does) grows exponentially with `ORDER`, so entries
+        // here are allocated lazily by `insert` instead.
+        Self {
+            nodes: HashMap::new(),
+        }
+    }
+}
+
+impl<const ORDER: usize> Graph<([PunchKind; ORDER], PunchKind), Weight> {
+    pub fn insert(&mut self, context: [PunchKind; ORDER], next: PunchKind) -> Option<Weight> {
+        let next_weight = self.nodes.entry((context, next)).or_insert(Weight::new(0));
+        let prev_val = *next_weight;
+
+        *next_weight += 1;
+
+        if prev_val == Weight::from(0) {
+            None
+        } else {
+            Some(prev_val)
+        }
+    }
+
+    /// Draws the next punch following `context`, treating its observed
+    /// outgoing edges as a weighted distribution. Unseen edges are treated
+    /// as zero weight rather than missing, since entries are lazily
+    /// allocated. Returns `None` when every outgoing weight is zero.
+    pub fn sample_next<R: rand::Rng>(
+        &self,
+        context: &[PunchKind; ORDER],
+        rng: &mut R,
+    ) -> Option<PunchKind> {
+        let weights = PunchKind::ALL.map(|next| {
+            let weight = self
+                .nodes
+                .get(&(*context, next))
+                .copied()
+                .unwrap_or(Weight::new(0));
+            (next, weight)
+        });
+        let total: usize = weights.iter().map(|(_, weight)| weight.as_usize()).sum();
+
+        if total == 0 {
+            return None;
+        }
+
+        let draw = rng.gen_range(0..total);
+        let mut cumulative = 0;
+        for (next, weight) in weights {
+            cumulative += weight.as_usize();
+            if draw < cumulative {
+                return Some(next);
+            }
+        }
+
+        unreachable!("cumulative weight never reached the drawn value")
+    }
+
+    /// Walks the chain for `len` punches starting with the context window
+    /// `start`, sliding the trailing-`ORDER` context forward as each punch
+    /// is emitted. Stops early if it reaches a context with no observed
+    /// outgoing transitions.
+    pub fn generate_combo<R: rand::Rng>(
+        &self,
+        start: [PunchKind; ORDER],
+        len: usize,
+        rng: &mut R,
+    ) -> Combo {
+        let mut punches = start.to_vec();
+        punches.truncate(len);
+
+        let mut context = start;
+        while punches.len() < len {
+            match self.sample_next(&context, rng) {
+                Some(next) => {
+                    punches.push(next);
+
+                    if ORDER > 0 {
+                        context.rotate_left(1);
+                        context[ORDER - 1] = next;
+                    }
+                }
+                None => break,
+            }
+        }
+
+        Combo::new(punches)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn should_generate_weighted_pairs() {
-        let mut graph: Graph<_, Weight> = Graph::new();
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
         for punch_kind in [PunchKind::Cross].into_iter().cycle().take(5) {
             graph.insert(&PunchKind::Jab, punch_kind);
         }
@@ -158,4 +453,220 @@ mod tests {
         let jab_cross = graph.nodes.get(&(PunchKind::Jab, PunchKind::Cross));
         assert_eq!(Some(&Weight::from(5)), jab_cross);
     }
+
+    #[test]
+    fn should_sample_next_deterministically_when_single_edge_is_trained() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+
+        let mut rng = rand::thread_rng();
+        let next = graph.sample_next(&PunchKind::Jab, &mut rng);
+
+        assert_eq!(Some(PunchKind::Cross), next);
+    }
+
+    #[test]
+    fn should_return_none_sampling_untrained_state() {
+        let graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        let mut rng = rand::thread_rng();
+
+        assert_eq!(None, graph.sample_next(&PunchKind::Jab, &mut rng));
+    }
+
+    #[test]
+    fn should_generate_combo_of_requested_length() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+        graph.insert(&PunchKind::Cross, PunchKind::Jab);
+
+        let mut rng = rand::thread_rng();
+        let combo = graph.generate_combo(PunchKind::Jab, 4, &mut rng);
+
+        assert_eq!(
+            Combo::new([
+                PunchKind::Jab,
+                PunchKind::Cross,
+                PunchKind::Jab,
+                PunchKind::Cross
+            ]),
+            combo
+        );
+    }
+
+    #[test]
+    fn should_stop_generating_combo_early_when_untrained() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+
+        let mut rng = rand::thread_rng();
+        let combo = graph.generate_combo(PunchKind::Jab, 5, &mut rng);
+
+        assert_eq!(Combo::new([PunchKind::Jab, PunchKind::Cross]), combo);
+    }
+
+    #[test]
+    fn should_drill_order_visit_every_punch_exactly_once() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+        graph.insert(&PunchKind::Jab, PunchKind::LeadHook);
+
+        let mut rng = rand::thread_rng();
+        let mut order = graph.drill_order(&PunchKind::Jab, &mut rng);
+        order.sort_by_key(|p| p.as_u8());
+
+        let mut expected = PunchKind::ALL;
+        expected.sort_by_key(|p| p.as_u8());
+
+        assert_eq!(expected.to_vec(), order);
+    }
+
+    #[test]
+    fn should_decode_most_likely_combo_along_dominant_edge() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        for _ in 0..10 {
+            graph.insert(&PunchKind::Jab, PunchKind::Cross);
+            graph.insert(&PunchKind::Cross, PunchKind::Jab);
+        }
+
+        let combo = graph.most_likely_combo(PunchKind::Jab, 4);
+
+        assert_eq!(
+            Some(Combo::new([
+                PunchKind::Jab,
+                PunchKind::Cross,
+                PunchKind::Jab,
+                PunchKind::Cross
+            ])),
+            combo
+        );
+    }
+
+    #[test]
+    fn should_return_none_decoding_untrained_start() {
+        let graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+
+        assert_eq!(None, graph.most_likely_combo(PunchKind::Jab, 4));
+    }
+
+    #[test]
+    fn should_count_higher_order_context_transitions() {
+        let mut model: MarkovModel<2> = MarkovModel::new();
+        for _ in 0..3 {
+            model.insert([PunchKind::Jab, PunchKind::Cross], PunchKind::LeadHook);
+        }
+
+        let weight = model
+            .nodes
+            .get(&([PunchKind::Jab, PunchKind::Cross], PunchKind::LeadHook));
+        assert_eq!(Some(&Weight::from(3)), weight);
+    }
+
+    #[test]
+    fn should_sample_next_deterministically_for_trained_context() {
+        let mut model: MarkovModel<2> = MarkovModel::new();
+        model.insert([PunchKind::Jab, PunchKind::Cross], PunchKind::LeadHook);
+
+        let mut rng = rand::thread_rng();
+        let next = model.sample_next(&[PunchKind::Jab, PunchKind::Cross], &mut rng);
+
+        assert_eq!(Some(PunchKind::LeadHook), next);
+    }
+
+    #[test]
+    fn should_return_none_sampling_untrained_context() {
+        let model: MarkovModel<2> = MarkovModel::new();
+        let mut rng = rand::thread_rng();
+
+        let next = model.sample_next(&[PunchKind::Jab, PunchKind::Cross], &mut rng);
+
+        assert_eq!(None, next);
+    }
+
+    #[test]
+    fn should_generate_combo_sliding_higher_order_context() {
+        let mut model: MarkovModel<2> = MarkovModel::new();
+        model.insert([PunchKind::Jab, PunchKind::Cross], PunchKind::LeadHook);
+        model.insert([PunchKind::Cross, PunchKind::LeadHook], PunchKind::Jab);
+
+        let mut rng = rand::thread_rng();
+        let combo = model.generate_combo([PunchKind::Jab, PunchKind::Cross], 4, &mut rng);
+
+        assert_eq!(
+            Combo::new([
+                PunchKind::Jab,
+                PunchKind::Cross,
+                PunchKind::LeadHook,
+                PunchKind::Jab
+            ]),
+            combo
+        );
+    }
+
+    #[test]
+    fn should_train_on_combo_windows() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        let combo = Combo::new([PunchKind::Jab, PunchKind::Cross, PunchKind::Jab]);
+
+        graph.train(&combo);
+
+        assert_eq!(
+            Some(&Weight::from(1)),
+            graph.nodes.get(&(PunchKind::Jab, PunchKind::Cross))
+        );
+        assert_eq!(
+            Some(&Weight::from(1)),
+            graph.nodes.get(&(PunchKind::Cross, PunchKind::Jab))
+        );
+    }
+
+    #[test]
+    fn should_be_a_noop_training_on_short_combo() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        let combo = Combo::new([PunchKind::Jab]);
+
+        graph.train(&combo);
+
+        assert_eq!(
+            Some(&Weight::from(0)),
+            graph.nodes.get(&(PunchKind::Jab, PunchKind::Jab))
+        );
+    }
+
+    #[test]
+    fn should_train_all_combos_in_a_batch() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        let combos = [
+            Combo::new([PunchKind::Jab, PunchKind::Cross]),
+            Combo::new([PunchKind::Jab, PunchKind::Cross]),
+        ];
+
+        graph.train_all(combos);
+
+        assert_eq!(
+            Some(&Weight::from(2)),
+            graph.nodes.get(&(PunchKind::Jab, PunchKind::Cross))
+        );
+    }
+
+    #[test]
+    fn should_build_normalized_outgoing_distribution() {
+        let mut graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+        graph.insert(&PunchKind::Jab, PunchKind::Cross);
+        graph.insert(&PunchKind::Jab, PunchKind::LeadHook);
+
+        let distribution = graph.outgoing_distribution(&PunchKind::Jab).unwrap();
+        let probabilities = distribution.probabilities();
+
+        assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn should_return_none_distribution_for_untrained_punch() {
+        let graph: Graph<(PunchKind, PunchKind), Weight> = Graph::new();
+
+        assert!(graph.outgoing_distribution(&PunchKind::Jab).is_none());
+    }
 }